@@ -1,21 +1,76 @@
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
 use std::process;
+use std::sync::Arc;
+use std::thread;
 
 use colored::*;
 use regex::Regex;
 
-/// Hard-coded English stopword list
-fn stopwords() -> HashSet<&'static str> {
-    [
-        "a", "an", "and", "are", "as", "at", "be", "but", "by",
-        "for", "if", "in", "into", "is", "it", "no", "not",
-        "of", "on", "or", "such", "that", "the", "their", "then",
-        "there", "these", "they", "this", "to", "was", "will", "with",
-    ]
-    .into_iter()
-    .collect()
+/// Bundled stopword lists, one `&'static str` per language, split at load time.
+const STOPWORDS_EN: &str = "\
+    a an and are as at be but by \
+    for if in into is it no not \
+    of on or such that the their then \
+    there these they this to was will with";
+
+const STOPWORDS_ES: &str = "\
+    a al algo algunas algunos ante antes como con contra cual cuando de del desde donde durante \
+    e el ella ellas ellos en entre era erais eramos eran es esa esas ese esos esta estas este estos \
+    fue fueron ha han hasta la las le les lo los mas mi mis mucho muy no nos nosotros o para pero \
+    poco por que quien se sin sobre su sus tambien te ti tu tus un una uno y ya";
+
+const STOPWORDS_FR: &str = "\
+    au aux avec ce ces dans de des du elle en et eux il je la le leur lui ma mais me meme mes moi \
+    mon ne nos notre nous on ou par pas pour qu que qui sa se ses son sur ta te tes toi ton tu un \
+    une vos votre vous";
+
+const STOPWORDS_DE: &str = "\
+    aber alle als also am an auch auf aus bei bin bis bist da damit dann der die das dem den des \
+    dessen die dies diese dieser dieses doch dort du durch ein eine einem einen einer eines er es \
+    euer eure fuer hatte hatten hier ich ihm ihn ihr ihre im in ist ja jede jedem jeden jeder jedes \
+    jener jetzt kann kein keine koennen machen man mehr mein meine mit muss nach nicht noch nun nur \
+    ob oder ohne sein seine sich sie sind so solche soll sollte und uns unser unter viel vom von vor \
+    war waren warst was weiter weitere wenn wer werde werden wie wieder will wir wird wirst wo zu zum \
+    zur zwar zwischen";
+
+/// Split a bundled list constant into an owned set of lowercase words.
+fn bundled_stopwords(lang: &str) -> HashSet<String> {
+    let list = match lang {
+        "es" => STOPWORDS_ES,
+        "fr" => STOPWORDS_FR,
+        "de" => STOPWORDS_DE,
+        _ => STOPWORDS_EN,
+    };
+    list.split_whitespace().map(|w| w.to_string()).collect()
+}
+
+/// Load a newline-separated custom stopword list from disk.
+fn load_stopwords_file(path: &str) -> HashSet<String> {
+    let contents = fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("{} {}: {}", "Could not read stopwords file".red().bold(), path, e);
+        process::exit(1);
+    });
+    contents
+        .lines()
+        .map(|line| line.trim().to_lowercase())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Resolve the active stopword set: the bundled list for `lang`, merged with
+/// a custom file's entries when one is given.
+fn resolve_stopwords(lang: &str, file: Option<&str>) -> HashSet<String> {
+    let mut set = bundled_stopwords(lang);
+    if let Some(path) = file {
+        set.extend(load_stopwords_file(path));
+    }
+    set
 }
 
 /// Tokenize text into words (lowercased, alphanumeric only)
@@ -36,6 +91,453 @@ fn generate_ngrams(tokens: &[String], n: usize) -> Vec<String> {
         .collect()
 }
 
+/// The category a `--mode=code:LANG` lexer assigns to a source token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum TokenClass {
+    Identifier,
+    Keyword,
+    StringLiteral,
+    Comment,
+    Number,
+    Operator,
+}
+
+impl TokenClass {
+    /// Plural label used in "Top {label}:" report headings.
+    fn label(&self) -> &'static str {
+        match self {
+            TokenClass::Identifier => "identifiers",
+            TokenClass::Keyword => "keywords",
+            TokenClass::StringLiteral => "string literals",
+            TokenClass::Comment => "comments",
+            TokenClass::Number => "numbers",
+            TokenClass::Operator => "operators",
+        }
+    }
+}
+
+impl std::str::FromStr for TokenClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "identifier" | "identifiers" => Ok(TokenClass::Identifier),
+            "keyword" | "keywords" => Ok(TokenClass::Keyword),
+            "stringliteral" | "string" | "strings" => Ok(TokenClass::StringLiteral),
+            "comment" | "comments" => Ok(TokenClass::Comment),
+            "number" | "numbers" => Ok(TokenClass::Number),
+            "operator" | "operators" => Ok(TokenClass::Operator),
+            other => Err(format!("unknown token class '{}'", other)),
+        }
+    }
+}
+
+/// A single classified token produced by `tokenize_code`.
+#[derive(Debug, Clone)]
+struct CodeToken {
+    text: String,
+    class: TokenClass,
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn",
+    "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let",
+    "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self",
+    "static", "struct", "super", "trait", "true", "try", "type", "unsafe", "use",
+    "where", "while",
+];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def",
+    "del", "elif", "else", "except", "False", "finally", "for", "from", "global",
+    "if", "import", "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass",
+    "raise", "return", "True", "try", "while", "with", "yield",
+];
+
+fn keywords_for(lang: &str) -> &'static [&'static str] {
+    match lang {
+        "python" | "py" => PYTHON_KEYWORDS,
+        _ => RUST_KEYWORDS,
+    }
+}
+
+/// Classify a source file into `Identifier`/`Keyword`/`StringLiteral`/`Comment`/
+/// `Number`/`Operator` tokens, the way a Pygments-style lexer would, instead of
+/// flattening everything with `tokenize`.
+fn tokenize_code(text: &str, lang: &str) -> Vec<CodeToken> {
+    let keywords = keywords_for(lang);
+    let hash_comments = lang == "python" || lang == "py";
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if !hash_comments && c == '/' && chars.get(i + 1) == Some(&'/') {
+            let start = i;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            tokens.push(CodeToken {
+                text: chars[start..i].iter().collect(),
+                class: TokenClass::Comment,
+            });
+        } else if !hash_comments && c == '/' && chars.get(i + 1) == Some(&'*') {
+            let start = i;
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i = (i + 2).min(chars.len());
+            tokens.push(CodeToken {
+                text: chars[start..i].iter().collect(),
+                class: TokenClass::Comment,
+            });
+        } else if hash_comments && c == '#' {
+            let start = i;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            tokens.push(CodeToken {
+                text: chars[start..i].iter().collect(),
+                class: TokenClass::Comment,
+            });
+        } else if c == '"' || c == '\'' {
+            let quote = c;
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i] != quote {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            tokens.push(CodeToken {
+                text: chars[start..i].iter().collect(),
+                class: TokenClass::StringLiteral,
+            });
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.' || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(CodeToken {
+                text: chars[start..i].iter().collect(),
+                class: TokenClass::Number,
+            });
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let class = if keywords.contains(&word.as_str()) {
+                TokenClass::Keyword
+            } else {
+                TokenClass::Identifier
+            };
+            tokens.push(CodeToken { text: word, class });
+        } else {
+            let start = i;
+            i += 1;
+            tokens.push(CodeToken {
+                text: chars[start..i].iter().collect(),
+                class: TokenClass::Operator,
+            });
+        }
+    }
+
+    tokens
+}
+
+/// Group classified code tokens by class and print a top-N bar chart for each.
+fn print_class_report(tokens: &[CodeToken], top_n: usize) {
+    let mut by_class: HashMap<TokenClass, Vec<String>> = HashMap::new();
+    tokens.iter().for_each(|t| {
+        by_class.entry(t.class).or_default().push(t.text.clone());
+    });
+
+    let mut classes: Vec<_> = by_class.keys().copied().collect();
+    classes.sort_by_key(|c| c.label());
+
+    classes.iter().for_each(|class| {
+        let frequencies = count_frequencies(&by_class[class]);
+        let mut sorted: Vec<_> = frequencies.into_iter().collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1));
+        if sorted.is_empty() {
+            return;
+        }
+        println!("\n{}", format!("Top {}:", class.label()).yellow().bold());
+        let top_items: Vec<_> = sorted.iter().take(top_n).cloned().collect();
+        display_bar_chart(&top_items, 40);
+    });
+}
+
+/// A leaf predicate recognised inside a `--where` expression.
+#[derive(Debug, Clone)]
+enum Predicate {
+    LenGt(usize),
+    LenLt(usize),
+    LenEq(usize),
+    StartsWith(char),
+    EndsWith(char),
+    Matches(Regex),
+    Stopword,
+    Numeric,
+    Alpha,
+}
+
+impl Predicate {
+    /// Stopwords are stored lowercased (see `resolve_stopwords`), so every
+    /// site that compares a word against the set — this one included — must
+    /// normalize the word's case first or it'll silently miss non-lowercase
+    /// tokens like code identifiers.
+    fn eval(&self, word: &str, stopwords: &HashSet<String>) -> bool {
+        match self {
+            Predicate::LenGt(n) => word.len() > *n,
+            Predicate::LenLt(n) => word.len() < *n,
+            Predicate::LenEq(n) => word.len() == *n,
+            Predicate::StartsWith(c) => word.starts_with(*c),
+            Predicate::EndsWith(c) => word.ends_with(*c),
+            Predicate::Matches(re) => re.is_match(word),
+            Predicate::Stopword => stopwords.contains(&word.to_lowercase()),
+            Predicate::Numeric => !word.is_empty() && word.chars().all(|c| c.is_numeric()),
+            Predicate::Alpha => !word.is_empty() && word.chars().all(|c| c.is_alphabetic()),
+        }
+    }
+}
+
+/// AST for a `--where` boolean filter expression.
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Atom(Predicate),
+}
+
+impl Expr {
+    /// Evaluated lazily so `and`/`or` short-circuit like the rest of Rust.
+    fn eval(&self, word: &str, stopwords: &HashSet<String>) -> bool {
+        match self {
+            Expr::And(l, r) => l.eval(word, stopwords) && r.eval(word, stopwords),
+            Expr::Or(l, r) => l.eval(word, stopwords) || r.eval(word, stopwords),
+            Expr::Not(e) => !e.eval(word, stopwords),
+            Expr::Atom(p) => p.eval(word, stopwords),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum WhereToken {
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Atom(String),
+}
+
+/// Lex a `--where` expression into tokens tagged with their byte position,
+/// so parse errors can point back at the offending character.
+fn lex_where(src: &str) -> Result<Vec<(usize, WhereToken)>, (usize, String)> {
+    let chars: Vec<(usize, char)> = src.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push((pos, WhereToken::LParen));
+            i += 1;
+        } else if c == ')' {
+            tokens.push((pos, WhereToken::RParen));
+            i += 1;
+        } else if src[pos..].starts_with("matches:/") {
+            let body_start = pos + "matches:/".len();
+            let close = src[body_start..]
+                .find('/')
+                .map(|rel| body_start + rel)
+                .ok_or_else(|| (pos, "unterminated regex literal, expected closing '/'".to_string()))?;
+            tokens.push((pos, WhereToken::Atom(src[pos..=close].to_string())));
+            i = chars.iter().position(|&(p, _)| p > close).unwrap_or(chars.len());
+        } else {
+            let mut end = i;
+            while end < chars.len() && !chars[end].1.is_whitespace() && chars[end].1 != '(' && chars[end].1 != ')' {
+                end += 1;
+            }
+            let word_end = chars.get(end).map(|&(p, _)| p).unwrap_or(src.len());
+            let word = &src[pos..word_end];
+            tokens.push((
+                pos,
+                match word {
+                    "and" => WhereToken::And,
+                    "or" => WhereToken::Or,
+                    "not" => WhereToken::Not,
+                    _ => WhereToken::Atom(word.to_string()),
+                },
+            ));
+            i = end;
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parse a single atom (e.g. `len>3`, `starts:a`, `matches:/foo/`) into a `Predicate`.
+fn parse_predicate(atom: &str) -> Result<Predicate, String> {
+    if let Some(n) = atom.strip_prefix("len>") {
+        return n.parse().map(Predicate::LenGt).map_err(|_| format!("invalid integer in '{}'", atom));
+    }
+    if let Some(n) = atom.strip_prefix("len<") {
+        return n.parse().map(Predicate::LenLt).map_err(|_| format!("invalid integer in '{}'", atom));
+    }
+    if let Some(n) = atom.strip_prefix("len=") {
+        return n.parse().map(Predicate::LenEq).map_err(|_| format!("invalid integer in '{}'", atom));
+    }
+    if let Some(c) = atom.strip_prefix("starts:") {
+        return c
+            .chars()
+            .next()
+            .map(Predicate::StartsWith)
+            .ok_or_else(|| format!("missing character in '{}'", atom));
+    }
+    if let Some(c) = atom.strip_prefix("ends:") {
+        return c
+            .chars()
+            .next()
+            .map(Predicate::EndsWith)
+            .ok_or_else(|| format!("missing character in '{}'", atom));
+    }
+    if let Some(pattern) = atom.strip_prefix("matches:/").and_then(|s| s.strip_suffix('/')) {
+        return Regex::new(pattern)
+            .map(Predicate::Matches)
+            .map_err(|e| format!("invalid regex in '{}': {}", atom, e));
+    }
+    match atom {
+        "stopword" => Ok(Predicate::Stopword),
+        "numeric" => Ok(Predicate::Numeric),
+        "alpha" => Ok(Predicate::Alpha),
+        _ => Err(format!("unknown predicate atom '{}'", atom)),
+    }
+}
+
+/// Recursive-descent/Pratt parser: `not` binds tighter than `and`, which binds
+/// tighter than `or`; parentheses override both.
+struct WhereParser<'a> {
+    tokens: &'a [(usize, WhereToken)],
+    pos: usize,
+}
+
+impl<'a> WhereParser<'a> {
+    fn new(tokens: &'a [(usize, WhereToken)]) -> Self {
+        Self { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&(usize, WhereToken)> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<&(usize, WhereToken)> {
+        let tok = self.tokens.get(self.pos);
+        self.pos += 1;
+        tok
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, (usize, String)> {
+        let mut lhs = self.parse_and()?;
+        while matches!(self.peek(), Some((_, WhereToken::Or))) {
+            self.advance();
+            let rhs = self.parse_and()?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, (usize, String)> {
+        let mut lhs = self.parse_not()?;
+        while matches!(self.peek(), Some((_, WhereToken::And))) {
+            self.advance();
+            let rhs = self.parse_not()?;
+            lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, (usize, String)> {
+        if matches!(self.peek(), Some((_, WhereToken::Not))) {
+            self.advance();
+            let inner = self.parse_not()?;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, (usize, String)> {
+        match self.advance() {
+            Some((_, WhereToken::LParen)) => {
+                let inner = self.parse_or()?;
+                match self.advance() {
+                    Some((_, WhereToken::RParen)) => Ok(inner),
+                    Some((pos, _)) => Err((*pos, "expected ')'".to_string())),
+                    None => Err((0, "expected ')' before end of expression".to_string())),
+                }
+            }
+            Some((pos, WhereToken::Atom(text))) => {
+                parse_predicate(text).map(Expr::Atom).map_err(|msg| (*pos, msg))
+            }
+            Some((pos, tok)) => Err((*pos, format!("unexpected token {:?}", tok))),
+            None => Err((0, "unexpected end of expression".to_string())),
+        }
+    }
+
+    fn parse(mut self) -> Result<Expr, (usize, String)> {
+        let expr = self.parse_or()?;
+        if let Some((pos, tok)) = self.peek() {
+            return Err((*pos, format!("unexpected trailing token {:?}", tok)));
+        }
+        Ok(expr)
+    }
+}
+
+/// A single-token predicate, boxed so it can be shared across threads.
+type WordPredicate = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Compile a `--where` expression into a token predicate. An empty
+/// expression accepts every token. Parse errors are reported with their
+/// byte position and the process exits immediately rather than falling
+/// back to a default.
+fn compile_where(expr: &str, stopwords: HashSet<String>) -> WordPredicate {
+    let trimmed = expr.trim();
+    if trimmed.is_empty() {
+        return Box::new(|_| true);
+    }
+
+    let report = |pos: usize, msg: &str| -> ! {
+        eprintln!(
+            "{} at position {}: {}",
+            "Error parsing --where expression".red().bold(),
+            pos,
+            msg
+        );
+        process::exit(1);
+    };
+
+    let tokens = match lex_where(trimmed) {
+        Ok(tokens) => tokens,
+        Err((pos, msg)) => report(pos, &msg),
+    };
+    let ast = match WhereParser::new(&tokens).parse() {
+        Ok(ast) => ast,
+        Err((pos, msg)) => report(pos, &msg),
+    };
+
+    Box::new(move |word| ast.eval(word, &stopwords))
+}
+
 /// Count frequencies (functional fold)
 fn count_frequencies(words: &[String]) -> HashMap<String, usize> {
     words.iter().fold(HashMap::new(), |mut acc, w| {
@@ -55,69 +557,507 @@ fn display_bar_chart(items: &[(String, usize)], max_bar: usize) {
     }
 }
 
+/// Expand a list of input paths into a flat list of files, reading every
+/// regular file directly inside a directory path one level deep.
+fn collect_input_files(paths: &[String]) -> Vec<String> {
+    paths
+        .iter()
+        .flat_map(|p| {
+            let path = Path::new(p);
+            if path.is_dir() {
+                let mut entries: Vec<String> = fs::read_dir(path)
+                    .unwrap_or_else(|e| {
+                        eprintln!("{} {}: {}", "Could not read directory".red().bold(), p, e);
+                        process::exit(1);
+                    })
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|entry| entry.is_file())
+                    .map(|entry| entry.to_string_lossy().into_owned())
+                    .collect();
+                entries.sort();
+                entries
+            } else {
+                vec![p.clone()]
+            }
+        })
+        .collect()
+}
+
+/// Tokenize one document's contents and run it through the same filter
+/// pipeline as single-document mode, producing the term list (after n-grams)
+/// that corpus-wide frequency stats are computed from.
+fn build_document_terms(
+    contents: &str,
+    code_lang: &Option<String>,
+    only_class: Option<TokenClass>,
+    filters: &FilterChain,
+    ngrams: usize,
+) -> Vec<String> {
+    let mut tokens: Vec<String> = match code_lang {
+        Some(lang) => {
+            let mut toks = tokenize_code(contents, lang);
+            if let Some(class) = only_class {
+                toks.retain(|t| t.class == class);
+            }
+            toks.into_iter().map(|t| t.text).collect()
+        }
+        None => tokenize(contents),
+    };
+
+    tokens = filters.apply(tokens);
+
+    generate_ngrams(&tokens, ngrams)
+}
+
+/// Rank each document's terms by TF-IDF (`tf(t,d) * ln(N / (1 + df(t)))`) and
+/// print the most distinctive ones per document, reusing `display_bar_chart`
+/// by scaling scores into bar-proportional integers.
+fn tfidf_report(files: &[String], documents: &[Vec<String>], top_n: usize) {
+    let doc_count = documents.len() as f64;
+
+    let mut doc_freq: HashMap<String, usize> = HashMap::new();
+    documents.iter().for_each(|doc| {
+        let unique: HashSet<&String> = doc.iter().collect();
+        unique.into_iter().for_each(|term| {
+            *doc_freq.entry(term.clone()).or_insert(0) += 1;
+        });
+    });
+
+    documents.iter().enumerate().for_each(|(i, doc)| {
+        let tf = count_frequencies(doc);
+        let mut scores: Vec<(String, f64)> = tf
+            .into_iter()
+            .map(|(term, count)| {
+                let df = *doc_freq.get(&term).unwrap_or(&0) as f64;
+                let idf = (doc_count / (1.0 + df)).ln();
+                (term, count as f64 * idf)
+            })
+            .collect();
+        scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+
+        println!(
+            "\n{} {}",
+            "Document:".yellow().bold(),
+            files.get(i).map(String::as_str).unwrap_or("?").bold()
+        );
+
+        if scores.is_empty() {
+            println!("{}", "No terms matched the filter.".red());
+            return;
+        }
+
+        let top_items: Vec<(String, usize)> = scores
+            .iter()
+            .take(top_n)
+            .map(|(term, score)| (term.clone(), (score.max(0.0) * 1000.0).round() as usize))
+            .collect();
+        display_bar_chart(&top_items, 40);
+    });
+}
+
+/// All of the single-token filters a run can have active (stopwords/regex/
+/// min-length/starts-with/`--where`), combined into one chain. Every place
+/// that needs to filter a token stream — the streaming pipeline, the
+/// single-file code/prose path and its classified-token mirror, and the
+/// per-document TF-IDF pass — builds one of these from the parsed flags and
+/// shares it, so filter semantics (like stopwords being matched
+/// case-insensitively) can't drift between call sites the way they used to.
+struct FilterChain {
+    filter_stopwords: bool,
+    stopwords: HashSet<String>,
+    regex: Option<Regex>,
+    min_length: Option<usize>,
+    starts_with: Option<char>,
+    where_predicate: Option<WordPredicate>,
+}
+
+impl FilterChain {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        filter_stopwords: bool,
+        stopwords: HashSet<String>,
+        regex: Option<Regex>,
+        min_length: Option<usize>,
+        starts_with: Option<char>,
+        where_expr: Option<&str>,
+    ) -> Self {
+        let where_predicate = where_expr.map(|expr| compile_where(expr, stopwords.clone()));
+        FilterChain { filter_stopwords, stopwords, regex, min_length, starts_with, where_predicate }
+    }
+
+    /// Stopwords are stored lowercased, so this normalizes case before
+    /// checking membership; that matters for code tokens, where identifiers
+    /// keep their original case.
+    fn matches(&self, word: &str) -> bool {
+        if self.filter_stopwords && self.stopwords.contains(&word.to_lowercase()) {
+            return false;
+        }
+        if let Some(re) = &self.regex {
+            if !re.is_match(word) {
+                return false;
+            }
+        }
+        if let Some(n) = self.min_length {
+            if word.len() <= n {
+                return false;
+            }
+        }
+        if let Some(c) = self.starts_with {
+            if !word.starts_with(c) {
+                return false;
+            }
+        }
+        if let Some(pred) = &self.where_predicate {
+            if !pred(word) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn apply(&self, tokens: Vec<String>) -> Vec<String> {
+        tokens.into_iter().filter(|w| self.matches(w)).collect()
+    }
+
+    fn apply_classified(&self, tokens: &mut Vec<CodeToken>) {
+        tokens.retain(|t| self.matches(&t.text));
+    }
+}
+
+/// Byte offsets, snapped to line boundaries, splitting a file into up to
+/// `threads` roughly equal shards so each worker can seek straight to its
+/// slice instead of reading the whole file up front.
+fn shard_boundaries(path: &str, threads: usize) -> io::Result<Vec<(u64, u64)>> {
+    let file_len = fs::metadata(path)?.len();
+    if threads <= 1 || file_len == 0 {
+        return Ok(vec![(0, file_len)]);
+    }
+
+    let mut cuts = vec![0u64];
+    for i in 1..threads {
+        let approx = file_len * i as u64 / threads as u64;
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(approx))?;
+        let mut discarded = Vec::new();
+        BufReader::new(&mut file).read_until(b'\n', &mut discarded)?;
+        cuts.push((approx + discarded.len() as u64).min(file_len));
+    }
+    cuts.push(file_len);
+    cuts.dedup();
+
+    Ok(cuts.windows(2).map(|w| (w[0], w[1])).collect())
+}
+
+/// Stream-tokenize one byte range of the file, line by line, folding
+/// straight into a local frequency map instead of collecting a token or
+/// n-gram `Vec` for the shard. `carry` keeps the last `n - 1` filtered
+/// tokens around so n-grams spanning a line boundary aren't lost. Alongside
+/// the local counts, also hand back the shard's leading and trailing
+/// `n - 1` filtered tokens (`head`/`tail`) so the caller can stitch in the
+/// n-grams that straddle a *shard* boundary, which this function alone has
+/// no way to see.
+fn process_shard(
+    path: &str,
+    start: u64,
+    end: u64,
+    n: usize,
+    filters: &FilterChain,
+) -> (HashMap<String, usize>, usize, Vec<String>, Vec<String>) {
+    let mut file = File::open(path).expect("Could not reopen the file for a shard");
+    file.seek(SeekFrom::Start(start)).expect("Could not seek into the file");
+    let reader = BufReader::new(file.take(end - start));
+
+    let mut frequencies: HashMap<String, usize> = HashMap::new();
+    let mut total = 0usize;
+    let mut carry: Vec<String> = Vec::new();
+    let mut head: Vec<String> = Vec::new();
+    let keep = n.saturating_sub(1);
+
+    for line in reader.lines() {
+        let line = line.expect("Could not read a line from the file");
+        let words: Vec<String> = tokenize(&line).into_iter().filter(|w| filters.matches(w)).collect();
+        if words.is_empty() {
+            continue;
+        }
+
+        if head.len() < keep {
+            head.extend(words.iter().take(keep - head.len()).cloned());
+        }
+
+        let mut combined = carry;
+        combined.extend(words);
+
+        generate_ngrams(&combined, n).into_iter().for_each(|gram| {
+            *frequencies.entry(gram).or_insert(0) += 1;
+            total += 1;
+        });
+
+        carry = if combined.len() > keep {
+            combined.split_off(combined.len() - keep)
+        } else {
+            combined
+        };
+    }
+
+    (frequencies, total, head, carry)
+}
+
+/// Shard the file across `threads` workers, each folding its slice into a
+/// local frequency map, then merge the partials. This is how large inputs
+/// are counted without ever materializing the full token/n-gram list. A
+/// shard's carry only survives within that shard, so each worker also
+/// reports its leading and trailing `n - 1` tokens; a sequential pass over
+/// the ordered results then carries trailing tokens forward shard by shard
+/// to reconstruct the n-grams a shard boundary would otherwise have split,
+/// the same way `carry` already does across line boundaries inside one
+/// shard. A single pairwise stitch of immediate neighbours isn't enough: a
+/// run of shards each smaller than `n - 1` tokens needs the gap carried
+/// across all of them before it can close.
+fn run_streaming(
+    path: &str,
+    n: usize,
+    filters: Arc<FilterChain>,
+    threads: usize,
+) -> (HashMap<String, usize>, usize) {
+    let shards = shard_boundaries(path, threads).unwrap_or_else(|e| {
+        eprintln!("{} {}: {}", "Could not read file".red().bold(), path, e);
+        process::exit(1);
+    });
+
+    let handles: Vec<_> = shards
+        .into_iter()
+        .map(|(start, end)| {
+            let path = path.to_string();
+            let filters = Arc::clone(&filters);
+            thread::spawn(move || process_shard(&path, start, end, n, filters.as_ref()))
+        })
+        .collect();
+
+    let results: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().expect("a counting thread panicked"))
+        .collect();
+
+    let (mut frequencies, mut total) = results.iter().fold(
+        (HashMap::new(), 0usize),
+        |(mut frequencies, mut total), (local_frequencies, local_total, _, _)| {
+            local_frequencies.iter().for_each(|(term, count)| {
+                *frequencies.entry(term.clone()).or_insert(0) += count;
+            });
+            total += local_total;
+            (frequencies, total)
+        },
+    );
+
+    if n > 1 {
+        // Walk the shards in order, carrying forward whatever trailing
+        // tokens haven't yet formed a full n-gram. A shard whose own token
+        // count never reached `n - 1` (`head` shorter than `keep`) can't
+        // resolve anything by itself, so its entire token stream folds into
+        // `pending` and the gap keeps growing until a later shard supplies
+        // enough tokens to close it — this is what a pairwise head/tail
+        // stitch over immediate neighbors misses for runs of small shards.
+        let keep = n - 1;
+        let mut pending: Vec<String> = Vec::new();
+        results.iter().for_each(|(_, _, head, tail)| {
+            if head.len() < keep {
+                pending.extend(head.iter().cloned());
+                if pending.len() >= n {
+                    generate_ngrams(&pending, n).into_iter().for_each(|gram| {
+                        *frequencies.entry(gram).or_insert(0) += 1;
+                        total += 1;
+                    });
+                    pending = pending.split_off(pending.len() - keep);
+                }
+            } else {
+                if !pending.is_empty() {
+                    let mut boundary = pending.clone();
+                    boundary.extend(head.iter().cloned());
+                    generate_ngrams(&boundary, n).into_iter().for_each(|gram| {
+                        *frequencies.entry(gram).or_insert(0) += 1;
+                        total += 1;
+                    });
+                }
+                pending = tail.clone();
+            }
+        });
+    }
+
+    (frequencies, total)
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
         eprintln!(
-            "Usage: {} <file-path> [--top=N] [--match-regex=PATTERN] [--ngrams=N] [--filter-stopwords] [--min-length=N] [--starts-with=C]",
+            "Usage: {} <file-path>... [--tfidf] [--top=N] [--match-regex=PATTERN] [--ngrams=N] [--filter-stopwords] [--stopwords-lang=LANG] [--stopwords-file=PATH] [--show-stopwords] [--min-length=N] [--starts-with=C] [--where=EXPR] [--mode=code:LANG] [--only-class=CLASS] [--threads=N]",
             args[0]
         );
         process::exit(1);
     }
 
-    let filename = &args[1];
+    let input_paths: Vec<String> = args.iter().skip(1).filter(|a| !a.starts_with("--")).cloned().collect();
+    if input_paths.is_empty() {
+        eprintln!("{}", "No input file or directory given.".red());
+        process::exit(1);
+    }
+
     let mut top_n = 10;
     let mut regex_filter: Option<Regex> = None;
     let mut ngrams = 1;
-    let mut filter_stopwords = false;
+    let mut filter_stopwords_flag = false;
+    let mut stopwords_lang = "en".to_string();
+    let mut stopwords_file: Option<String> = None;
+    let mut show_stopwords = false;
     let mut min_length: Option<usize> = None;
     let mut starts_with: Option<char> = None;
+    let mut where_expr: Option<String> = None;
+    let mut code_lang: Option<String> = None;
+    let mut only_class: Option<TokenClass> = None;
+    let mut tfidf = false;
+    let mut threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
 
     // Parse optional flags
-    args.iter().skip(2).for_each(|arg| {
-        if let Some(n) = arg.strip_prefix("--top=") {
+    args.iter().skip(1).filter(|a| a.starts_with("--")).for_each(|arg| {
+        if arg == "--tfidf" {
+            tfidf = true;
+        } else if let Some(n) = arg.strip_prefix("--top=") {
             top_n = n.parse().unwrap_or(10);
         } else if let Some(pattern) = arg.strip_prefix("--match-regex=") {
             regex_filter = Regex::new(pattern).ok();
         } else if let Some(n) = arg.strip_prefix("--ngrams=") {
             ngrams = n.parse().unwrap_or(1);
         } else if arg == "--filter-stopwords" {
-            filter_stopwords = true;
+            filter_stopwords_flag = true;
+        } else if let Some(lang) = arg.strip_prefix("--stopwords-lang=") {
+            stopwords_lang = lang.to_string();
+        } else if let Some(path) = arg.strip_prefix("--stopwords-file=") {
+            stopwords_file = Some(path.to_string());
+        } else if arg == "--show-stopwords" {
+            show_stopwords = true;
         } else if let Some(n) = arg.strip_prefix("--min-length=") {
             min_length = n.parse().ok();
         } else if let Some(c) = arg.strip_prefix("--starts-with=") {
             starts_with = c.chars().next();
+        } else if let Some(expr) = arg.strip_prefix("--where=") {
+            where_expr = Some(expr.to_string());
+        } else if let Some(mode) = arg.strip_prefix("--mode=") {
+            code_lang = mode.strip_prefix("code:").map(|lang| lang.to_lowercase());
+        } else if let Some(class) = arg.strip_prefix("--only-class=") {
+            only_class = Some(class.parse().unwrap_or_else(|e: String| {
+                eprintln!("{} {}", "Invalid --only-class value:".red().bold(), e);
+                process::exit(1);
+            }));
+        } else if let Some(n) = arg.strip_prefix("--threads=") {
+            threads = n.parse().unwrap_or(threads).max(1);
         }
     });
 
-    // Read file
-    let contents = fs::read_to_string(filename).expect("Could not read the file");
+    if only_class.is_some() && code_lang.is_none() {
+        eprintln!(
+            "{}",
+            "--only-class requires --mode=code:LANG; it has no effect on plain-prose input.".red().bold()
+        );
+        process::exit(1);
+    }
 
-    // Tokenize
-    let mut tokens = tokenize(&contents);
+    let active_stopwords = resolve_stopwords(&stopwords_lang, stopwords_file.as_deref());
 
-    // Apply stopword filter
-    if filter_stopwords {
-        let sw = stopwords();
-        tokens = tokens
-            .into_iter()
-            .filter(|w| !sw.contains(w.as_str()))
+    if show_stopwords {
+        let mut words: Vec<_> = active_stopwords.iter().cloned().collect();
+        words.sort();
+        println!("{} {}", "Active stopwords:".green(), words.join(", "));
+    }
+
+    let files = collect_input_files(&input_paths);
+
+    let filters = Arc::new(FilterChain::new(
+        filter_stopwords_flag,
+        active_stopwords.clone(),
+        regex_filter.clone(),
+        min_length,
+        starts_with,
+        where_expr.as_deref(),
+    ));
+
+    if tfidf {
+        let documents: Vec<Vec<String>> = files
+            .iter()
+            .map(|path| {
+                let contents = fs::read_to_string(path).expect("Could not read the file");
+                build_document_terms(&contents, &code_lang, only_class, &filters, ngrams)
+            })
             .collect();
+
+        println!("{}", "TF-IDF Corpus Analysis".bold().underline().cyan());
+        println!("{} {}", "Documents:".green(), files.len());
+        tfidf_report(&files, &documents, top_n);
+        return;
     }
 
-    // Apply regex filter
-    if let Some(re) = &regex_filter {
-        tokens = tokens.into_iter().filter(|w| re.is_match(w)).collect();
+    if files.len() > 1 {
+        eprintln!(
+            "{} {} more file(s) ignored without --tfidf; analyzing {} only.",
+            "Warning:".yellow().bold(),
+            files.len() - 1,
+            files[0]
+        );
     }
+    let filename = &files[0];
+
+    // Plain prose goes through the streaming pipeline: the file is sharded
+    // across worker threads and folded straight into a frequency map, so a
+    // multi-gigabyte input never needs its full token/n-gram list in memory.
+    // Code mode still reads the whole file, since its lexer needs to see
+    // constructs (block comments, multi-line strings) that span lines.
+    if code_lang.is_none() {
+        let (frequencies, total) = run_streaming(filename, ngrams, Arc::clone(&filters), threads);
+
+        let mut sorted: Vec<_> = frequencies.into_iter().collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1));
+
+        println!("{}", "Text Analysis Report".bold().underline().cyan());
+        println!("{} {}", "Total items:".green(), total);
+        println!("{} {}", "Unique items:".green(), sorted.len());
 
-    // Apply min-length filter
-    if let Some(n) = min_length {
-        tokens = tokens.into_iter().filter(|w| w.len() > n).collect();
+        if let Some((word, count)) = sorted.first() {
+            println!("{} {} ({})", "Most common:".yellow().bold(), word.bold(), count);
+        }
+
+        if !sorted.is_empty() {
+            println!("\n{}", format!("Top {} frequent:", top_n).yellow().bold());
+            let top_items: Vec<_> = sorted.iter().take(top_n).cloned().collect();
+            display_bar_chart(&top_items, 40);
+        } else {
+            println!("{}", "No words matched the filter.".red());
+        }
+
+        return;
     }
 
-    // Apply starts-with filter
-    if let Some(c) = starts_with {
-        tokens = tokens.into_iter().filter(|w| w.starts_with(c)).collect();
+    // Read file
+    let contents = fs::read_to_string(filename).expect("Could not read the file");
+
+    // Tokenize: either a code-aware lexer (classified tokens) or plain prose
+    let mut classified: Option<Vec<CodeToken>> = code_lang.as_ref().map(|lang| {
+        let mut toks = tokenize_code(&contents, lang);
+        if let Some(class) = only_class {
+            toks.retain(|t| t.class == class);
+        }
+        toks
+    });
+    let mut tokens: Vec<String> = match &classified {
+        Some(toks) => toks.iter().map(|t| t.text.clone()).collect(),
+        None => tokenize(&contents),
+    };
+
+    tokens = filters.apply(tokens);
+
+    // Mirror the same filters onto the classified tokens so the per-class
+    // report below reflects what actually reached the frequency count
+    if let Some(toks) = &mut classified {
+        filters.apply_classified(toks);
     }
 
     // Generate N-grams
@@ -151,4 +1091,8 @@ fn main() {
     } else {
         println!("{}", "No words matched the filter.".red());
     }
+
+    if let Some(toks) = &classified {
+        print_class_report(toks, top_n);
+    }
 }